@@ -1,12 +1,18 @@
 use std::fmt;
-use std::fs::File;
-use std::io::{prelude::*, BufReader};
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Ok, Result};
 use clap::{ArgEnum, Parser, Subcommand};
-use redis::Commands;
 use url::Url;
 
+mod parsing;
+mod store;
+
+use parsing::{read_lines_lossy, tuple2_from_split, LineFailure};
+#[cfg(test)]
+use store::MockStore;
+use store::{RedisStore, Store};
+
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct Cli {
@@ -21,6 +27,14 @@ struct Cli {
     #[clap(long, default_value_t = String::from(""))]
     redis_pwd: String,
 
+    /// Connect over TLS (uses the `rediss://` scheme).
+    #[clap(long)]
+    redis_tls: bool,
+
+    /// Redis logical database index to select after connecting.
+    #[clap(long)]
+    redis_db: Option<i64>,
+
     /// Target filename to be loaded.
     #[clap(long, short, forbid_empty_values = true)]
     file: String,
@@ -28,8 +42,11 @@ struct Cli {
     /// feishu url
     #[clap(long, validator = validate_url)]
     feishu_url: Option<String>,
-}
 
+    /// Number of Redis commands to accumulate before flushing a pipeline.
+    #[clap(long, default_value_t = 1000)]
+    batch_size: usize,
+}
 
 fn validate_url(url: &str) -> std::result::Result<(), String> {
     match Url::parse(url) {
@@ -85,10 +102,91 @@ enum SignalType {
     ClickRate,
 }
 
+impl fmt::Display for SignalType {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", format!("{:?}", self).to_lowercase())
+    }
+}
+
 const REDIS_CFG_KEY_EXP_EXP_AB_PARAMS: &str = "cfg:exp:ab";
 const REDIS_KEY_EXP_ADID_DEFALUT_CHOICE: &str = "exp:default:adid:choices";
 const REDIS_CFG_KEY_EXP_TARGET_CTR_ACTION: &str = "cfg:exp:action:targetctr:default";
 const REDIS_CFG_KEY_EXP_VERSION_AD_ID_SCORES: &str = "expversion:score:default";
+const REDIS_CFG_KEY_EXP_RANGE_SIGNAL: &str = "cfg:exp:range:signal";
+
+/// Counters reported back to the caller (and to Feishu) once a load finishes.
+#[derive(Default)]
+struct LoadStats {
+    written: usize,
+    failures: Vec<LineFailure>,
+}
+
+impl LoadStats {
+    fn skipped(&self) -> usize {
+        self.failures.len()
+    }
+}
+
+/// Prints the per-line diagnostics collected while loading `command`, if any.
+fn report_failures(command: &str, stats: &LoadStats) {
+    if stats.failures.is_empty() {
+        return;
+    }
+
+    eprintln!(
+        "{} line(s) failed to load during {}:",
+        stats.failures.len(),
+        command
+    );
+    for failure in &stats.failures {
+        eprintln!("  line {}: {}", failure.line, failure.message);
+    }
+}
+
+/// Builds a Redis connection spec from the CLI flags, including the
+/// password, TLS scheme and logical database index, and connects.
+///
+/// The password is set via `Url::set_password` rather than spliced into the
+/// string by hand, so it gets percent-encoded and may safely contain `/`,
+/// `#`, `?` and similar characters.
+fn connect(cli: &Cli) -> Result<redis::Connection> {
+    let scheme = if cli.redis_tls { "rediss" } else { "redis" };
+
+    let url = build_redis_url(scheme, &cli.redis_addr, &cli.redis_pwd, cli.redis_db)?;
+
+    let client = redis::Client::open(url.as_str())?;
+    Ok(client.get_connection()?)
+}
+
+/// Builds the Redis connection URL for `scheme://[:pwd@]addr[/db]`,
+/// percent-encoding `pwd` via `Url::set_password` instead of splicing it
+/// into the string by hand.
+fn build_redis_url(scheme: &str, addr: &str, pwd: &str, db: Option<i64>) -> Result<Url> {
+    let mut url = Url::parse(&format!("{}://{}", scheme, addr))
+        .map_err(|err| anyhow!("invalid redis address {:?}: {}", addr, err))?;
+
+    if !pwd.is_empty() {
+        url.set_password(Some(pwd))
+            .map_err(|_| anyhow!("invalid redis password"))?;
+    }
+    if let Some(db) = db {
+        url.set_path(&format!("/{}", db));
+    }
+
+    Ok(url)
+}
+
+/// Connects to Redis and runs `f` against a fresh `RedisStore`, so a
+/// `connect()` failure flows back through `f`'s `Result` instead of
+/// bypassing it.
+fn run_with_store(
+    cli: &Cli,
+    f: impl FnOnce(&mut RedisStore) -> Result<LoadStats>,
+) -> Result<LoadStats> {
+    let mut con = connect(cli)?;
+    let mut store = RedisStore::new(&mut con, cli.batch_size);
+    f(&mut store)
+}
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
@@ -97,136 +195,539 @@ fn main() -> Result<()> {
     println!("redis_addr: {:?}", cli.redis_addr);
     println!("file: {:?}", cli.file);
 
-    match cli.command {
+    let command_desc = format!("{:?}", cli.command);
+    let started_at = Instant::now();
+
+    let result = match cli.command {
         Command::AbParams { types } => {
             println!("AbParams types: {:?}", types);
-            handle_ab_params(&cli, types)
+            run_with_store(&cli, |store| handle_ab_params(&cli, types, store))
         }
         Command::ActionChoice => {
             println!("ActionChoice");
-            handle_action_choice(&cli)
+            run_with_store(&cli, |store| handle_action_choice(&cli, store))
         }
         Command::ActionScore => {
             println!("ActionScore");
-            handle_action_score(&cli)
+            run_with_store(&cli, |store| handle_action_score(&cli, store))
         }
         Command::ActionValue => {
             println!("ActionValue");
-            handle_action_value(&cli)
+            run_with_store(&cli, |store| handle_action_value(&cli, store))
         }
         Command::RangeSignal { types } => {
             println!("types: {:?}", types);
-            handle_range_signal(&cli, types)
+            run_with_store(&cli, |store| handle_range_signal(&cli, types, store))
         }
+    };
+
+    if let std::result::Result::Ok(stats) = &result {
+        report_failures(&command_desc, stats);
     }
-}
 
-fn handle_ab_params(cli: &Cli, types: AbType) -> Result<()> {
-    let client = redis::Client::open(format!("redis://{}", cli.redis_addr))?;
-    let mut con = client.get_connection()?;
+    if let Some(feishu_url) = &cli.feishu_url {
+        notify_feishu(
+            feishu_url,
+            &command_desc,
+            &cli.file,
+            started_at.elapsed(),
+            &result,
+        );
+    }
+
+    match result {
+        std::result::Result::Ok(stats) if stats.failures.is_empty() => Ok(()),
+        std::result::Result::Ok(stats) => Err(anyhow!(
+            "{} line(s) failed to load; see diagnostics above",
+            stats.failures.len()
+        )),
+        std::result::Result::Err(err) => Err(err),
+    }
+}
 
-    let file = File::open(&format!("{}", cli.file))?;
-    let reader = BufReader::new(file);
+/// Posts a one-line-per-field run summary to a Feishu incoming webhook.
+///
+/// Failures to notify are logged but never surface as an error from `main`,
+/// since the load itself already succeeded or failed on its own merits.
+fn notify_feishu(
+    url: &str,
+    command: &str,
+    file: &str,
+    elapsed: Duration,
+    result: &Result<LoadStats>,
+) {
+    let text = match result {
+        std::result::Result::Ok(stats) => format!(
+            "command: {}\nfile: {}\nwritten: {}\nskipped: {}\nelapsed: {:.2?}",
+            command,
+            file,
+            stats.written,
+            stats.skipped(),
+            elapsed
+        ),
+        std::result::Result::Err(err) => format!(
+            "command: {}\nfile: {}\nelapsed: {:.2?}\nerror: {}",
+            command, file, elapsed, err
+        ),
+    };
+
+    let body = serde_json::json!({
+        "msg_type": "text",
+        "content": { "text": text },
+    });
+
+    if let std::result::Result::Err(err) = ureq::post(url).send_json(body) {
+        eprintln!("failed to post feishu summary: {}", err);
+    }
+}
 
-    for line in reader.lines() {
-        let line = line?;
-        let kv = tuple2_from_split(&line.as_str(), '=', "bad line");
-        if kv.is_ok() {
-            let kv = kv.unwrap();
-            con.hset(
-                REDIS_CFG_KEY_EXP_EXP_AB_PARAMS,
-                format!("{}:{}", kv.0, types),
-                kv.1,
-            )?;
+fn handle_ab_params(cli: &Cli, types: AbType, store: &mut impl Store) -> Result<LoadStats> {
+    let (lines, failures) = read_lines_lossy(&cli.file)?;
+    let mut stats = LoadStats {
+        written: 0,
+        failures,
+    };
+
+    for (idx, line) in lines.iter().enumerate() {
+        match tuple2_from_split(line, '=', "bad line") {
+            std::result::Result::Ok(kv) => {
+                store.hset(
+                    REDIS_CFG_KEY_EXP_EXP_AB_PARAMS,
+                    &format!("{}:{}", kv.0, types),
+                    &kv.1,
+                )?;
+                stats.written += 1;
+            }
+            std::result::Result::Err(err) => stats.failures.push(LineFailure {
+                line: idx + 1,
+                message: err.to_string(),
+            }),
         }
     }
+    store.flush()?;
 
-    Ok(())
+    Ok(stats)
 }
 
-fn handle_action_choice(cli: &Cli) -> Result<()> {
-    let client = redis::Client::open(format!("redis://{}", cli.redis_addr))?;
-    let mut con = client.get_connection()?;
-
-    let file = File::open(&format!("{}", cli.file))?;
-    let reader = BufReader::new(file);
+fn handle_action_choice(cli: &Cli, store: &mut impl Store) -> Result<LoadStats> {
+    let (lines, mut failures) = read_lines_lossy(&cli.file)?;
 
     let mut items = vec![];
 
-    for line in reader.lines() {
-        let line = line?;
-        let kv = tuple2_from_split(&line.as_str(), '=', "bad line")?;
-        // con.hset(
-        //     RedisKey_ExpAdidDefalutChoice,
-        //     format!("{}:{}", kv.0, kv.1),
-        //     kv.1,
-        // )?;
-        items.push(kv);
+    for (idx, line) in lines.iter().enumerate() {
+        match tuple2_from_split(line, '=', "bad line") {
+            std::result::Result::Ok(kv) => {
+                // store.hset(
+                //     RedisKey_ExpAdidDefalutChoice,
+                //     format!("{}:{}", kv.0, kv.1),
+                //     kv.1,
+                // )?;
+                items.push(kv);
+            }
+            std::result::Result::Err(err) => failures.push(LineFailure {
+                line: idx + 1,
+                message: err.to_string(),
+            }),
+        }
     }
     if !items.is_empty() {
-        con.hset_multiple(REDIS_KEY_EXP_ADID_DEFALUT_CHOICE, &items)?;
+        store.hset_multiple(REDIS_KEY_EXP_ADID_DEFALUT_CHOICE, &items)?;
     }
+    store.flush()?;
 
-    Ok(())
+    Ok(LoadStats {
+        written: items.len(),
+        failures,
+    })
 }
 
-fn handle_action_score(cli: &Cli) -> Result<()> {
-    let client = redis::Client::open(format!("redis://{}", cli.redis_addr))?;
-    let mut con = client.get_connection()?;
-
-    let file = File::open(&format!("{}", cli.file))?;
-    let reader = BufReader::new(file);
+fn handle_action_score(cli: &Cli, store: &mut impl Store) -> Result<LoadStats> {
+    let (lines, failures) = read_lines_lossy(&cli.file)?;
+    let mut stats = LoadStats {
+        written: 0,
+        failures,
+    };
+
+    for (idx, line) in lines.iter().enumerate() {
+        let line_no = idx + 1;
+
+        let kv = match tuple2_from_split(line, '=', "bad line") {
+            std::result::Result::Ok(kv) => kv,
+            std::result::Result::Err(err) => {
+                stats.failures.push(LineFailure {
+                    line: line_no,
+                    message: err.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let values: Vec<f32> = match serde_json::from_str(&kv.1) {
+            std::result::Result::Ok(values) => values,
+            std::result::Result::Err(err) => {
+                stats.failures.push(LineFailure {
+                    line: line_no,
+                    message: format!("invalid JSON: {}", err),
+                });
+                continue;
+            }
+        };
 
-    for line in reader.lines() {
-        let line = line?;
-        let kv = tuple2_from_split(&line.as_str(), '=', "bad line")?;
-        let values: Vec<f32> = serde_json::from_str(&kv.1)?;
         let mut items = vec![];
-
         for (action_id, val) in values.iter().enumerate() {
             items.push((format!("{}", action_id), format!("{}", val)))
         }
-        con.hset_multiple(
-            format!("{}:{}", REDIS_CFG_KEY_EXP_VERSION_AD_ID_SCORES, kv.0),
+        stats.written += items.len();
+        store.hset_multiple(
+            &format!("{}:{}", REDIS_CFG_KEY_EXP_VERSION_AD_ID_SCORES, kv.0),
             &items,
         )?;
     }
-    Ok(())
+    store.flush()?;
+    Ok(stats)
 }
 
-fn handle_action_value(cli: &Cli) -> Result<()> {
-    let client = redis::Client::open(format!("redis://{}", cli.redis_addr))?;
-    let mut con = client.get_connection()?;
-
-    let mut file = File::open(&format!("{}", cli.file))?;
+fn handle_action_value(cli: &Cli, store: &mut impl Store) -> Result<LoadStats> {
+    let bytes = std::fs::read(&cli.file)?;
+    let data = String::from_utf8_lossy(&bytes);
 
-    let mut data = String::new();
-    file.read_to_string(&mut data)?;
     let kv = tuple2_from_split(&data, '=', "bad line")?;
     let values: Vec<f32> = serde_json::from_str(&kv.1)?;
 
     for (action_id, val) in values.iter().enumerate() {
-        con.hset(
+        store.hset(
             REDIS_CFG_KEY_EXP_TARGET_CTR_ACTION,
-            format!("{}", action_id),
-            format!("{}", val),
+            &format!("{}", action_id),
+            &format!("{}", val),
         )?;
     }
+    store.flush()?;
 
-    Ok(())
+    Ok(LoadStats {
+        written: values.len(),
+        failures: Vec::new(),
+    })
 }
 
-fn handle_range_signal(cli: &Cli, types: SignalType) -> Result<()> {
-    todo!();
+fn handle_range_signal(cli: &Cli, types: SignalType, store: &mut impl Store) -> Result<LoadStats> {
+    let (lines, failures) = read_lines_lossy(&cli.file)?;
+    let mut stats = LoadStats {
+        written: 0,
+        failures,
+    };
+
+    for (idx, line) in lines.iter().enumerate() {
+        match tuple2_from_split(line, '=', "bad line") {
+            std::result::Result::Ok(kv) => {
+                store.hset(
+                    REDIS_CFG_KEY_EXP_RANGE_SIGNAL,
+                    &format!("{}:{}", kv.0, types),
+                    &kv.1,
+                )?;
+                stats.written += 1;
+            }
+            std::result::Result::Err(err) => stats.failures.push(LineFailure {
+                line: idx + 1,
+                message: err.to_string(),
+            }),
+        }
+    }
+    store.flush()?;
+
+    Ok(stats)
 }
 
-fn tuple2_from_split<'a>(value: &'a str, pat: char, msg: &'static str) -> Result<(String, String)> {
-    let mut split = value.split(pat);
-    let v1 = split.next().ok_or_else(|| anyhow!(msg))?.to_owned();
-    let v2 = split.next().ok_or_else(|| anyhow!(msg))?.to_owned();
-    if split.next().is_some() {
-        return Err(anyhow!(msg));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+
+    fn write_temp_file(name: &str, contents: &str) -> String {
+        write_temp_bytes(name, contents.as_bytes())
+    }
+
+    fn write_temp_bytes(name: &str, contents: &[u8]) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "ads-params-cli-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let mut file = File::create(&path).unwrap();
+        file.write_all(contents).unwrap();
+        path.to_str().unwrap().to_owned()
+    }
+
+    fn test_cli(file: String) -> Cli {
+        Cli {
+            command: Command::ActionChoice,
+            redis_addr: String::new(),
+            redis_pwd: String::new(),
+            redis_tls: false,
+            redis_db: None,
+            file,
+            feishu_url: None,
+            batch_size: 1000,
+        }
+    }
+
+    #[test]
+    fn build_redis_url_plain() {
+        let url = build_redis_url("redis", "127.0.0.1:6379", "", None).unwrap();
+        assert_eq!(url.as_str(), "redis://127.0.0.1:6379");
+    }
+
+    #[test]
+    fn build_redis_url_percent_encodes_password() {
+        let url = build_redis_url("redis", "127.0.0.1:6379", "aB3/xJ9+Q==", None).unwrap();
+        assert_eq!(url.password(), Some("aB3%2FxJ9+Q%3D%3D"));
+        // The crucial regression check: redis::Client::open must actually
+        // accept the built URL for a password containing `/`.
+        redis::Client::open(url.as_str()).unwrap();
+    }
+
+    #[test]
+    fn build_redis_url_percent_encodes_special_characters() {
+        let url = build_redis_url("redis", "127.0.0.1:6379", "p@ss#w?rd", None).unwrap();
+        assert_eq!(url.password(), Some("p%40ss%23w%3Frd"));
+        redis::Client::open(url.as_str()).unwrap();
+    }
+
+    #[test]
+    fn build_redis_url_tls_and_db_index() {
+        let url = build_redis_url("rediss", "127.0.0.1:6379", "secret", Some(3)).unwrap();
+        assert_eq!(url.as_str(), "rediss://:secret@127.0.0.1:6379/3");
+    }
+
+    #[test]
+    fn ab_params_writes_expected_fields() {
+        let file = write_temp_file("ab_params", "a=1\nb=2\n");
+        let cli = test_cli(file.clone());
+        let mut store = MockStore::default();
+
+        let stats = handle_ab_params(&cli, AbType::Fill, &mut store).unwrap();
+
+        assert_eq!(stats.written, 2);
+        assert_eq!(stats.skipped(), 0);
+        assert_eq!(
+            store.writes,
+            vec![
+                (
+                    REDIS_CFG_KEY_EXP_EXP_AB_PARAMS.to_owned(),
+                    "a:fill".to_owned(),
+                    "1".to_owned()
+                ),
+                (
+                    REDIS_CFG_KEY_EXP_EXP_AB_PARAMS.to_owned(),
+                    "b:fill".to_owned(),
+                    "2".to_owned()
+                ),
+            ]
+        );
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn ab_params_skips_malformed_lines() {
+        let file = write_temp_file("ab_params_bad", "a=1\nbadline\nb=2\n");
+        let cli = test_cli(file.clone());
+        let mut store = MockStore::default();
+
+        let stats = handle_ab_params(&cli, AbType::Click, &mut store).unwrap();
+
+        assert_eq!(stats.written, 2);
+        assert_eq!(stats.skipped(), 1);
+        assert_eq!(stats.failures[0].line, 2);
+        std::fs::remove_file(file).unwrap();
     }
 
-    Ok((v1, v2))
+    #[test]
+    fn ab_params_empty_file() {
+        let file = write_temp_file("ab_params_empty", "");
+        let cli = test_cli(file.clone());
+        let mut store = MockStore::default();
+
+        let stats = handle_ab_params(&cli, AbType::Show, &mut store).unwrap();
+
+        assert_eq!(stats.written, 0);
+        assert_eq!(stats.skipped(), 0);
+        assert!(store.writes.is_empty());
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn action_choice_writes_multi_with_trailing_newline() {
+        let file = write_temp_file("action_choice", "x=1\ny=2\n");
+        let cli = test_cli(file.clone());
+        let mut store = MockStore::default();
+
+        let stats = handle_action_choice(&cli, &mut store).unwrap();
+
+        assert_eq!(stats.written, 2);
+        assert_eq!(
+            store.writes,
+            vec![
+                (
+                    REDIS_KEY_EXP_ADID_DEFALUT_CHOICE.to_owned(),
+                    "x".to_owned(),
+                    "1".to_owned()
+                ),
+                (
+                    REDIS_KEY_EXP_ADID_DEFALUT_CHOICE.to_owned(),
+                    "y".to_owned(),
+                    "2".to_owned()
+                ),
+            ]
+        );
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn action_choice_reports_malformed_line_and_continues() {
+        let file = write_temp_file("action_choice_bad", "x=1\nbadline\ny=2\n");
+        let cli = test_cli(file.clone());
+        let mut store = MockStore::default();
+
+        let stats = handle_action_choice(&cli, &mut store).unwrap();
+
+        assert_eq!(stats.written, 2);
+        assert_eq!(stats.skipped(), 1);
+        assert_eq!(stats.failures[0].line, 2);
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn action_score_writes_one_field_per_action_id() {
+        let file = write_temp_file("action_score", "adid1=[0.1,0.2]\n");
+        let cli = test_cli(file.clone());
+        let mut store = MockStore::default();
+
+        let stats = handle_action_score(&cli, &mut store).unwrap();
+
+        assert_eq!(stats.written, 2);
+        assert_eq!(
+            store.writes,
+            vec![
+                (
+                    format!("{}:{}", REDIS_CFG_KEY_EXP_VERSION_AD_ID_SCORES, "adid1"),
+                    "0".to_owned(),
+                    "0.1".to_owned()
+                ),
+                (
+                    format!("{}:{}", REDIS_CFG_KEY_EXP_VERSION_AD_ID_SCORES, "adid1"),
+                    "1".to_owned(),
+                    "0.2".to_owned()
+                ),
+            ]
+        );
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn action_value_writes_global_targets() {
+        let file = write_temp_file("action_value", "all=[0.3,0.4,0.5]");
+        let cli = test_cli(file.clone());
+        let mut store = MockStore::default();
+
+        let stats = handle_action_value(&cli, &mut store).unwrap();
+
+        assert_eq!(stats.written, 3);
+        assert_eq!(
+            store.writes,
+            vec![
+                (
+                    REDIS_CFG_KEY_EXP_TARGET_CTR_ACTION.to_owned(),
+                    "0".to_owned(),
+                    "0.3".to_owned()
+                ),
+                (
+                    REDIS_CFG_KEY_EXP_TARGET_CTR_ACTION.to_owned(),
+                    "1".to_owned(),
+                    "0.4".to_owned()
+                ),
+                (
+                    REDIS_CFG_KEY_EXP_TARGET_CTR_ACTION.to_owned(),
+                    "2".to_owned(),
+                    "0.5".to_owned()
+                ),
+            ]
+        );
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn action_score_reports_invalid_json_and_continues() {
+        let file = write_temp_file(
+            "action_score_bad",
+            "adid1=[0.1,0.2]\nadid2=not-json\nadid3=[0.5]\n",
+        );
+        let cli = test_cli(file.clone());
+        let mut store = MockStore::default();
+
+        let stats = handle_action_score(&cli, &mut store).unwrap();
+
+        assert_eq!(stats.written, 3);
+        assert_eq!(stats.skipped(), 1);
+        assert_eq!(stats.failures[0].line, 2);
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn range_signal_writes_expected_fields() {
+        let file = write_temp_file("range_signal", "a=1\nb=2\n");
+        let cli = test_cli(file.clone());
+        let mut store = MockStore::default();
+
+        let stats = handle_range_signal(&cli, SignalType::FillRate, &mut store).unwrap();
+
+        assert_eq!(stats.written, 2);
+        assert_eq!(stats.skipped(), 0);
+        assert_eq!(
+            store.writes,
+            vec![
+                (
+                    REDIS_CFG_KEY_EXP_RANGE_SIGNAL.to_owned(),
+                    "a:fillrate".to_owned(),
+                    "1".to_owned()
+                ),
+                (
+                    REDIS_CFG_KEY_EXP_RANGE_SIGNAL.to_owned(),
+                    "b:fillrate".to_owned(),
+                    "2".to_owned()
+                ),
+            ]
+        );
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn range_signal_skips_malformed_lines() {
+        let file = write_temp_file("range_signal_bad", "a=1\nbadline\nb=2\n");
+        let cli = test_cli(file.clone());
+        let mut store = MockStore::default();
+
+        let stats = handle_range_signal(&cli, SignalType::TemptClick, &mut store).unwrap();
+
+        assert_eq!(stats.written, 2);
+        assert_eq!(stats.skipped(), 1);
+        assert_eq!(stats.failures[0].line, 2);
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn read_lines_lossy_reports_invalid_utf8_and_continues() {
+        let mut contents = b"a=1\n".to_vec();
+        contents.extend_from_slice(&[0xFF, 0xFE]);
+        contents.extend_from_slice(b"\nb=2");
+        let path = write_temp_bytes("invalid_utf8", &contents);
+
+        let (lines, failures) = read_lines_lossy(&path).unwrap();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "a=1");
+        assert_eq!(lines[2], "b=2");
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].line, 2);
+        std::fs::remove_file(path).unwrap();
+    }
 }
@@ -0,0 +1,54 @@
+use anyhow::{anyhow, Result};
+
+/// A single line that failed to parse or load. Callers collect these into a
+/// diagnostics list instead of aborting the whole file on the first bad line.
+pub struct LineFailure {
+    pub line: usize,
+    pub message: String,
+}
+
+pub fn tuple2_from_split(value: &str, pat: char, msg: &'static str) -> Result<(String, String)> {
+    let mut split = value.split(pat);
+    let v1 = split.next().ok_or_else(|| anyhow!(msg))?.to_owned();
+    let v2 = split.next().ok_or_else(|| anyhow!(msg))?.to_owned();
+    if split.next().is_some() {
+        return Err(anyhow!(msg));
+    }
+
+    Ok((v1, v2))
+}
+
+/// Reads `path` fully and splits it into lines, tolerating a missing
+/// trailing newline. Invalid UTF-8 is lossy-decoded rather than aborting
+/// the read, so a single corrupt byte can't kill a multi-thousand-line
+/// load; each line that needed lossy decoding is reported back once as a
+/// `LineFailure` carrying its (1-based) line number.
+pub fn read_lines_lossy(path: &str) -> Result<(Vec<String>, Vec<LineFailure>)> {
+    let bytes = std::fs::read(path)?;
+    if bytes.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    let mut segments: Vec<&[u8]> = bytes.split(|&b| b == b'\n').collect();
+    if bytes.last() == Some(&b'\n') {
+        segments.pop();
+    }
+
+    let mut lines = Vec::with_capacity(segments.len());
+    let mut failures = Vec::new();
+
+    for (idx, segment) in segments.into_iter().enumerate() {
+        match std::str::from_utf8(segment) {
+            Ok(s) => lines.push(s.to_owned()),
+            Err(_) => {
+                failures.push(LineFailure {
+                    line: idx + 1,
+                    message: "invalid UTF-8; lossy-decoded and continued".to_owned(),
+                });
+                lines.push(String::from_utf8_lossy(segment).into_owned());
+            }
+        }
+    }
+
+    Ok((lines, failures))
+}
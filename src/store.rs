@@ -0,0 +1,93 @@
+use anyhow::Result;
+
+/// A place ad-params get written to: the real Redis connection during
+/// normal runs, or an in-memory recorder in tests.
+pub trait Store {
+    fn hset(&mut self, key: &str, field: &str, value: &str) -> Result<()>;
+    fn hset_multiple(&mut self, key: &str, items: &[(String, String)]) -> Result<()>;
+    fn flush(&mut self) -> Result<()>;
+}
+
+/// Accumulates Redis commands into a `redis::pipe()` and flushes them in a
+/// single round-trip once `batch_size` commands are pending, instead of
+/// issuing one `hset`/`hset_multiple` per input line. Each flush is wrapped
+/// in MULTI/EXEC so a batch applies atomically.
+pub struct RedisStore<'a> {
+    con: &'a mut redis::Connection,
+    batch_size: usize,
+    pipe: redis::Pipeline,
+    pending: usize,
+}
+
+impl<'a> RedisStore<'a> {
+    pub fn new(con: &'a mut redis::Connection, batch_size: usize) -> Self {
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        RedisStore {
+            con,
+            batch_size,
+            pipe,
+            pending: 0,
+        }
+    }
+
+    fn flush_if_full(&mut self) -> Result<()> {
+        if self.pending >= self.batch_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Store for RedisStore<'a> {
+    fn hset(&mut self, key: &str, field: &str, value: &str) -> Result<()> {
+        self.pipe.hset(key, field, value);
+        self.pending += 1;
+        self.flush_if_full()
+    }
+
+    fn hset_multiple(&mut self, key: &str, items: &[(String, String)]) -> Result<()> {
+        self.pipe.hset_multiple(key, items);
+        self.pending += 1;
+        self.flush_if_full()
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if self.pending > 0 {
+            self.pipe.query::<()>(self.con)?;
+            self.pipe = redis::pipe();
+            self.pipe.atomic();
+            self.pending = 0;
+        }
+        Ok(())
+    }
+}
+
+/// In-memory `Store` used by tests; records every write as a
+/// `(key, field, value)` triple in call order.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockStore {
+    pub writes: Vec<(String, String, String)>,
+}
+
+#[cfg(test)]
+impl Store for MockStore {
+    fn hset(&mut self, key: &str, field: &str, value: &str) -> Result<()> {
+        self.writes
+            .push((key.to_owned(), field.to_owned(), value.to_owned()));
+        Ok(())
+    }
+
+    fn hset_multiple(&mut self, key: &str, items: &[(String, String)]) -> Result<()> {
+        for (field, value) in items {
+            self.writes
+                .push((key.to_owned(), field.clone(), value.clone()));
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}